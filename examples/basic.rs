@@ -2,9 +2,7 @@ use std::time::Instant;
 
 use macroquad::prelude as mqr;
 use mqr::*;
-use rusty_skelform as skf;
-use rusty_skelform_macroquad as skf_mq;
-use skf::time_frame;
+use rusty_skelform_macroquad::{animate, draw_props, load_skelform_armature, AnimOptions, BoneIndex};
 
 pub const ARMATURE_NIL: &str = "Armature not found! Please run this in the 'examples' folder.";
 pub const INSTRUCTIONS: &str =
@@ -14,8 +12,8 @@ pub const INSTRUCTIONS: &str =
 async fn main() {
     // load SkelForm armature
     let armature_filename = "skellington.skf";
-    let (mut skellington, skel_texes) = skf_mq::load(armature_filename);
-    let (mut skellina, skela_texes) = skf_mq::load("skellina.skf");
+    let (mut skellington, skel_tex, skel_bones) = load_skelform_armature(armature_filename);
+    let (mut skellina, skela_tex, _) = load_skelform_armature("skellina.skf");
 
     // timer for animations
     let mut time = Instant::now();
@@ -84,20 +82,15 @@ async fn main() {
         draw_skellington(
             time,
             &mut skellington,
+            &skel_bones,
             anim_idx,
             pos,
             dir,
-            &skel_texes,
+            &skel_tex,
             skel_scale,
         );
 
-        draw_skellina(
-            skela_time,
-            &mut skellina,
-            skel_scale,
-            &skela_texes,
-            ground_y,
-        );
+        draw_skellina(skela_time, &mut skellina, skel_scale, &skela_tex, ground_y);
 
         let white = Color::from_rgba(255, 255, 255, 255);
         if skellington.bones.len() == 0 {
@@ -112,76 +105,93 @@ async fn main() {
 
 fn draw_skellington(
     time: std::time::Instant,
-    armature: &mut skf::Armature,
+    armature: &mut rusty_skelform::Armature,
+    bones: &BoneIndex,
     anim_idx: usize,
     pos: Vec2,
     dir: f32,
-    texes: &Vec<mqr::Texture2D>,
+    tex: &Texture2D,
     skel_scale: f32,
 ) {
-    // process animation(s)
-    let tf0 = time_frame(time, &armature.animations[anim_idx], false, true);
-    let skel_options = skf_mq::ConstructOptions {
-        speed: 1.,
-        scale: mqr::Vec2::new(skel_scale * dir, skel_scale),
-        position: Vec2::new(pos.x, pos.y),
-        ..Default::default()
-    };
-    skf_mq::animate(
-        &mut armature.bones,
-        &vec![&armature.animations[anim_idx]],
-        &vec![tf0],
-        &vec![20],
-    );
-
-    // these will be used later for immutable edits before construction
-    let mut armature_c = armature.clone();
-    let bones = &mut armature_c.bones;
-
-    // move shoulder and head targets to mouse
-    let mouse = skf::Vec2::new(
+    // move shoulder and head targets to the mouse, before animating, so the IK solve reaches
+    // for it; looking these up by name via BoneIndex keeps this working if the rig is
+    // re-exported and bone order shifts
+    let mouse = rusty_skelform::Vec2::new(
         mouse_position().0 / skel_scale * dir,
         -mouse_position().1 / skel_scale,
     );
-    bones[0].pos = skf::Vec2::new(-pos.x / skel_scale * dir, pos.y / skel_scale) + mouse;
-    bones[4].pos = skf::Vec2::new(-pos.x / skel_scale * dir, pos.y / skel_scale) + mouse;
+    let target = rusty_skelform::Vec2::new(-pos.x / skel_scale * dir, pos.y / skel_scale) + mouse;
+
+    // edits are made on a per-frame copy, so the flips below don't accumulate across frames
+    let mut posed = armature.clone();
+    if let Some(head_target) = bones.bone_mut(&mut posed, "HeadTarget") {
+        head_target.pos = target;
+    }
+    if let Some(shoulder_target) = bones.bone_mut(&mut posed, "ShoulderTarget") {
+        shoulder_target.pos = target;
+    }
 
     // flip skull and hat if looking the other way
     if (dir == 1. && mouse_position().0 < pos.x) || (dir != 1. && mouse_position().0 > pos.x) {
-        let skull = bones.iter_mut().find(|b| b.name == "Skull").unwrap();
-        skull.scale.y = -skull.scale.y;
-        let hat = bones.iter_mut().find(|b| b.name == "Hat").unwrap();
-        hat.rot = -hat.rot;
-        let shoulder = bones.iter_mut().find(|b| b.name == "LSIK").unwrap();
-        shoulder.ik_constraint = 1;
+        if let Some(skull) = bones.bone_mut(&mut posed, "Skull") {
+            skull.scale.y = -skull.scale.y;
+        }
+        if let Some(hat) = bones.bone_mut(&mut posed, "Hat") {
+            hat.rot = -hat.rot;
+        }
+        if let Some(shoulder) = bones.bone_mut(&mut posed, "LSIK") {
+            shoulder.ik_constraint = 1;
+        }
     }
 
-    // construct and draw armature
-    let mut constructed_bones = skf_mq::construct(&armature_c, skel_options);
-    skf_mq::draw(&mut constructed_bones, &texes, &vec![&armature_c.styles[0]]);
+    let (mut props, _) = animate(
+        &mut posed,
+        tex,
+        anim_idx,
+        Some(time),
+        true,
+        false,
+        Some(AnimOptions {
+            scale_factor: skel_scale,
+            pos_offset: Vec2::new(pos.x, pos.y),
+            ..Default::default()
+        }),
+    );
+
+    // mirror around the character's own position to face the walk direction
+    for prop in &mut props {
+        prop.pos.x = pos.x + (prop.pos.x - pos.x) * dir;
+        prop.scale.x *= dir;
+    }
+    draw_props(&mut props, &posed, tex, None);
 }
 
 fn draw_skellina(
     time: std::time::Instant,
-    skellina: &mut skf::Armature,
+    skellina: &mut rusty_skelform::Armature,
     scale: f32,
-    texes: &Vec<mqr::Texture2D>,
+    tex: &Texture2D,
     ground_y: f32,
 ) {
-    let tf0 = time_frame(time, &skellina.animations[0], false, true);
-    skf_mq::animate(
-        &mut skellina.bones,
-        &vec![&skellina.animations[0]],
-        &vec![tf0],
-        &vec![0],
-    );
-    let mut bones = skf_mq::construct(
-        &skellina,
-        skf_mq::ConstructOptions {
-            scale: mqr::Vec2::new(-scale, scale),
-            position: mqr::Vec2::new(screen_width() - 100., ground_y + 50.),
+    let pos = Vec2::new(screen_width() - 100., ground_y + 50.);
+    let (mut props, _) = animate(
+        skellina,
+        tex,
+        0,
+        Some(time),
+        true,
+        false,
+        Some(AnimOptions {
+            scale_factor: scale,
+            pos_offset: pos,
             ..Default::default()
-        },
+        }),
     );
-    skf_mq::draw(&mut bones, &texes, &vec![&skellina.styles[0]]);
+
+    // skellina faces left
+    for prop in &mut props {
+        prop.pos.x = pos.x - (prop.pos.x - pos.x);
+        prop.scale.x *= -1.;
+    }
+    draw_props(&mut props, skellina, tex, None);
 }
@@ -0,0 +1,145 @@
+//! Velocity-synced locomotion. [`GaitDriver`] advances a walk/run cycle by distance actually
+//! traveled rather than wall-clock time, so one stride always covers one `stride_length`.
+
+use crate::{animate, shortest_angle_delta, AnimOptions};
+use macroquad::prelude::{Texture2D, Vec2};
+use rusty_skelform::{Armature, Bone};
+
+/// Drives a walk (and optionally a run) clip by distance moved rather than wall-clock time.
+pub struct GaitDriver {
+    /// World units covered by one full playthrough of the walk clip.
+    pub stride_length: f32,
+    pub walk_animation_index: usize,
+    pub run_animation_index: Option<usize>,
+    /// Horizontal speed at which the run clip fully replaces the walk clip.
+    pub run_speed: f32,
+    phase: f32,
+}
+
+impl GaitDriver {
+    pub fn new(stride_length: f32, walk_animation_index: usize) -> Self {
+        GaitDriver {
+            stride_length,
+            walk_animation_index,
+            run_animation_index: None,
+            run_speed: 1.,
+            phase: 0.,
+        }
+    }
+
+    pub fn with_run(mut self, run_animation_index: usize, run_speed: f32) -> Self {
+        self.run_animation_index = Some(run_animation_index);
+        self.run_speed = run_speed;
+        self
+    }
+
+    /// Current position in the gait cycle, in whole (unwrapped) cycles.
+    pub fn phase(&self) -> f32 {
+        self.phase
+    }
+
+    /// Advance the gait cycle by the horizontal distance moved this frame and sample the
+    /// resulting pose, cross-fading from walk into run as the horizontal speed
+    /// (`velocity.x.abs()`) approaches `run_speed`. Does not render; composite the result with [`crate::draw_props`] yourself,
+    /// optionally after overlaying an upper-body pose with [`overlay_subtree`].
+    pub fn update(
+        &mut self,
+        armature: &mut Armature,
+        texture: &Texture2D,
+        velocity: Vec2,
+        dt: f32,
+    ) -> Vec<Bone> {
+        self.phase += velocity.x.abs() * dt / self.stride_length;
+
+        let walk_frame = phase_to_frame(armature, self.walk_animation_index, self.phase);
+        let (walk_props, _) = animate(
+            armature,
+            texture,
+            self.walk_animation_index,
+            None,
+            true,
+            false,
+            Some(AnimOptions {
+                frame: Some(walk_frame),
+                ..Default::default()
+            }),
+        );
+
+        let Some(run_animation_index) = self.run_animation_index else {
+            return walk_props;
+        };
+
+        let t = (velocity.x.abs() / self.run_speed).clamp(0., 1.);
+        if t <= 0. {
+            return walk_props;
+        }
+
+        let run_frame = phase_to_frame(armature, run_animation_index, self.phase);
+        let (run_props, _) = animate(
+            armature,
+            texture,
+            run_animation_index,
+            None,
+            true,
+            false,
+            Some(AnimOptions {
+                frame: Some(run_frame),
+                ..Default::default()
+            }),
+        );
+
+        walk_props
+            .iter()
+            .zip(run_props.iter())
+            .map(|(walk, run)| {
+                let mut blended = walk.clone();
+                blended.pos = walk.pos.lerp(run.pos, t);
+                blended.scale = walk.scale.lerp(run.scale, t);
+                blended.rot = walk.rot + shortest_angle_delta(walk.rot, run.rot) * t;
+                blended
+            })
+            .collect()
+    }
+}
+
+/// Map a (possibly multi-cycle) gait phase to a looping frame number within `animation_index`.
+fn phase_to_frame(armature: &Armature, animation_index: usize, phase: f32) -> i32 {
+    let frame_count = armature.animations[animation_index]
+        .keyframes
+        .iter()
+        .map(|kf| kf.frame)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    (phase.fract().abs() * frame_count as f32) as i32
+}
+
+/// Overlay `overlay`'s pose onto `base` for every bone in the subtree rooted at
+/// `root_bone_name` (inclusive), leaving the rest of `base` untouched. Use this to drive an
+/// upper-body aim/reach animation independently of a [`GaitDriver`]-controlled lower body.
+pub fn overlay_subtree(armature: &Armature, base: &mut [Bone], overlay: &[Bone], root_bone_name: &str) {
+    let Some(root_idx) = armature.bones.iter().position(|b| b.name == root_bone_name) else {
+        return;
+    };
+
+    for i in 0..base.len() {
+        if is_descendant_or_self(armature, i, root_idx) {
+            base[i] = overlay[i].clone();
+        }
+    }
+}
+
+fn is_descendant_or_self(armature: &Armature, idx: usize, root_idx: usize) -> bool {
+    let mut current = idx as i32;
+    loop {
+        if current == root_idx as i32 {
+            return true;
+        }
+        let parent = armature.bones[current as usize].parent_id;
+        if parent < 0 || parent as usize == current as usize {
+            return false;
+        }
+        current = parent;
+    }
+}
@@ -0,0 +1,168 @@
+//! Optional GPU-skinned batch renderer.
+//!
+//! [`GpuSkinner`] combines a run of meshed bones into one mesh and one draw call, uploading
+//! each bone's 2D transform into a uniform array and skinning in the vertex shader.
+//! [`crate::draw_props_gpu`] batches each contiguous run of meshed bones separately, so an
+//! armature whose meshed bones are split by sprite bones takes one draw call per run, not one
+//! in total. A run with more meshed bones than `max_gpu_bones` falls back to the CPU path;
+//! shorter runs of the same armature are still batched.
+
+use macroquad::material::{gl_use_default_material, gl_use_material, load_material, Material, MaterialParams};
+use macroquad::math::Vec2;
+use macroquad::miniquad::{ShaderSource, UniformDesc, UniformType};
+use macroquad::models::{draw_mesh, Mesh};
+use macroquad::texture::Texture2D;
+use rusty_skelform::{Armature, Bone};
+
+use crate::Lights;
+
+/// Sane default bone-count ceiling for [`GpuSkinner`]. Chosen to stay well within the uniform
+/// vector slots available on constrained backends like WebGL/GLES2; this is a fixed budget, not
+/// a detected one, so tune it down yourself if you're targeting one of those backends.
+pub const DEFAULT_MAX_GPU_BONES: usize = 64;
+
+const VERTEX_SHADER: &str = "#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+attribute vec4 color0;
+
+varying lowp vec2 uv;
+varying lowp vec4 color;
+
+uniform mat4 Model;
+uniform mat4 Projection;
+uniform vec2 bone_scale[MAX_GPU_BONES];
+uniform vec2 bone_pos[MAX_GPU_BONES];
+
+void main() {
+    int bone_idx = int(position.z);
+    vec2 scale = bone_scale[bone_idx];
+    vec2 pos = position.xy * scale + bone_pos[bone_idx];
+
+    gl_Position = Projection * Model * vec4(pos, 0.0, 1.0);
+    uv = texcoord;
+    color = color0;
+}
+";
+
+const FRAGMENT_SHADER: &str = "#version 100
+varying lowp vec2 uv;
+varying lowp vec4 color;
+
+uniform sampler2D Texture;
+
+void main() {
+    gl_FragColor = color * texture2D(Texture, uv);
+}
+";
+
+/// Compiles the batched-skinning material and holds the bone-count ceiling it was built for.
+pub struct GpuSkinner {
+    material: Material,
+    max_gpu_bones: usize,
+}
+
+impl GpuSkinner {
+    /// Compile the skinning shader for up to `max_gpu_bones` bones per draw call.
+    pub fn new(max_gpu_bones: usize) -> Self {
+        let vertex = VERTEX_SHADER.replace("MAX_GPU_BONES", &max_gpu_bones.to_string());
+
+        let material = load_material(
+            ShaderSource::Glsl {
+                vertex: &vertex,
+                fragment: FRAGMENT_SHADER,
+            },
+            MaterialParams {
+                uniforms: vec![
+                    UniformDesc::new("bone_scale", UniformType::Float2).array(max_gpu_bones),
+                    UniformDesc::new("bone_pos", UniformType::Float2).array(max_gpu_bones),
+                ],
+                ..Default::default()
+            },
+        )
+        .expect("GpuSkinner: failed to compile batch-skinning shader");
+
+        GpuSkinner {
+            material,
+            max_gpu_bones,
+        }
+    }
+
+    pub fn max_gpu_bones(&self) -> usize {
+        self.max_gpu_bones
+    }
+
+    /// Batch `bones` (meshed bones the caller has already picked out, e.g. one contiguous run
+    /// of an armature's draw order) into a single draw call. `lights`, if given, is sampled
+    /// per-vertex, same as [`crate::create_mesh`]. Returns `false` (drawing nothing) when
+    /// `bones` is empty or exceeds `max_gpu_bones`, so the caller can fall back to the CPU path
+    /// for that run.
+    pub fn draw(&self, bones: &[&Bone], armature: &Armature, tex: &Texture2D, lights: Option<&Lights>) -> bool {
+        if bones.is_empty() || bones.len() > self.max_gpu_bones {
+            return false;
+        }
+
+        let style = &armature.styles[0];
+
+        let mut bone_scale = vec![Vec2::new(1., 1.); self.max_gpu_bones];
+        let mut bone_pos = vec![Vec2::new(0., 0.); self.max_gpu_bones];
+
+        let mut mesh = Mesh {
+            vertices: vec![],
+            indices: vec![],
+            texture: Some(tex.clone()),
+        };
+
+        for (slot, bone) in bones.iter().enumerate() {
+            // matches `create_mesh`: meshed bones are only ever scaled/translated, never
+            // rotated, so the two paths stay visually identical
+            bone_scale[slot] = bone.scale;
+            bone_pos[slot] = bone.pos;
+
+            let bone_tex = &style.textures[bone.tex_idx as usize];
+            let base_index = mesh.vertices.len() as u16;
+
+            for i in &bone.indices {
+                mesh.indices.push(base_index + *i as u16);
+            }
+
+            for v in &bone.vertices {
+                let offset = Vec2::new(
+                    (v.pos.x - bone_tex.size.x / 2.) / 2.,
+                    (-v.pos.y - bone_tex.size.y / 2.) / 2.,
+                );
+
+                let color = match lights {
+                    Some(lights) => {
+                        // light sampling needs the rendered (scaled) offset, same as `create_mesh` -
+                        // the shader applies `bone.scale` separately when actually placing the vertex
+                        let scaled_offset = offset * bone.scale;
+                        let world_pos = bone.pos + scaled_offset;
+                        let tint = lights.sample(world_pos);
+                        let rim = lights.rim(world_pos, scaled_offset);
+                        macroquad::color::Color::new(
+                            (tint.r + rim.r).min(1.),
+                            (tint.g + rim.g).min(1.),
+                            (tint.b + rim.b).min(1.),
+                            1.,
+                        )
+                    }
+                    None => macroquad::color::WHITE,
+                };
+
+                mesh.vertices.push(macroquad::models::Vertex::new(
+                    offset.x, offset.y, slot as f32, v.uv.x, v.uv.y, color,
+                ));
+            }
+        }
+
+        self.material.set_uniform("bone_scale", bone_scale.as_slice());
+        self.material.set_uniform("bone_pos", bone_pos.as_slice());
+
+        gl_use_material(&self.material);
+        draw_mesh(&mesh);
+        gl_use_default_material();
+
+        true
+    }
+}
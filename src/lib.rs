@@ -7,7 +7,7 @@
 //! #[macroquad::main("Demo")]
 //! async fn main() {
 //!     // Load SkelForm armature.
-//!     let (armature, tex) = load_skelform_armature("path_to_export", 0);
+//!     let (armature, tex, bones) = load_skelform_armature("path_to_export");
 //!
 //!     // Start a timer to use for the animation.
 //!     let time = std::time::Instant::now();
@@ -26,12 +26,35 @@ use macroquad::prelude::*;
 use rusty_skelform::*;
 use std::{collections::HashMap, io::Read, time::Instant};
 
+mod state_machine;
+pub use state_machine::{AnimState, StateMachine, Transition};
+
+pub mod gpu_skin;
+pub use gpu_skin::GpuSkinner;
+
+mod gait;
+pub use gait::{overlay_subtree, GaitDriver};
+
+mod lighting;
+pub use lighting::{Lights, PointLight};
+
+mod naming;
+pub use naming::{retarget, BoneIndex};
+
+mod ragdoll;
+pub use ragdoll::Ragdoll;
+
 /// Load a SkelForm armature.
 /// The file to load is the zip that is provided by SkelForm export.
-pub fn load_skelform_armature(zip_path: &str) -> (Armature, Texture2D) {
+///
+/// Returns a [`BoneIndex`] alongside the armature so user code can address bones by name
+/// (`bone_index`/`bone_mut`) instead of fragile position-based `bones[4]` lookups.
+pub fn load_skelform_armature(zip_path: &str) -> (Armature, Texture2D, BoneIndex) {
     // return an empty armature and texture if file doesn't exist
     if !std::fs::exists(zip_path).unwrap() {
-        return (Armature::default(), Texture2D::empty());
+        let armature = Armature::default();
+        let bone_index = BoneIndex::build(&armature);
+        return (armature, Texture2D::empty(), bone_index);
     }
 
     let file = std::fs::File::open(zip_path).unwrap();
@@ -56,7 +79,8 @@ pub fn load_skelform_armature(zip_path: &str) -> (Armature, Texture2D) {
         tex = Texture2D::from_file_with_format(&img, Some(ImageFormat::Png));
     }
 
-    (root.armature.clone(), tex)
+    let bone_index = BoneIndex::build(&root.armature);
+    (root.armature.clone(), tex, bone_index)
 }
 
 /// Load a SkelForm armature, but pointing to armature and texture data separately.
@@ -65,7 +89,7 @@ pub fn load_skelform_scattered(
     armature_path: &str,
     texture_path: &str,
     armature_idx: usize,
-) -> (Armature, Texture2D) {
+) -> (Armature, Texture2D, BoneIndex) {
     let file = std::fs::File::open(armature_path).unwrap();
     let root: SkelformRoot = serde_json::from_reader(&file).unwrap();
 
@@ -77,7 +101,8 @@ pub fn load_skelform_scattered(
             Texture2D::from_file_with_format(std::fs::read(texture_path).unwrap().as_slice(), None);
     }
 
-    (root.armature.clone(), tex)
+    let bone_index = BoneIndex::build(&root.armature);
+    (root.armature.clone(), tex, bone_index)
 }
 
 #[derive(PartialEq)]
@@ -94,6 +119,15 @@ pub struct AnimOptions {
 
     pub last_anim_idx: usize,
     pub last_anim_frame: i32,
+
+    /// How long (in seconds) to cross-fade from `last_anim_idx` into the newly requested
+    /// animation. The blend is timed off of the same `time` passed to `animate()`, so reset
+    /// it the same way you already do when switching animations. Set to 0 to disable blending.
+    pub blend_duration: f32,
+
+    /// Point lights to tint the rendered props with. `None` renders at full white, same as
+    /// before lighting support existed.
+    pub lights: Option<Lights>,
 }
 
 impl Default for AnimOptions {
@@ -105,6 +139,8 @@ impl Default for AnimOptions {
             frame: None,
             last_anim_idx: usize::MAX,
             last_anim_frame: 0,
+            blend_duration: 0.15,
+            lights: None,
         }
     }
 }
@@ -119,6 +155,8 @@ impl Default for AnimOptions {
 /// `frame` - Render only this particular frame.
 /// `last_anim_idx` - Index of the last animation that was played. Used for blending.
 /// `last_anim_frame` - The frame of the last animation to blend from. Set to -1 for last frame.
+/// `blend_duration` - Seconds to cross-fade from `last_anim_idx` into `animation_index`.
+/// `lights` - Point lights to tint the rendered props with.
 ///
 /// Note: edits to the armature (head following cursor, etc) should be made *before* calling `animate()`, unless processing the props manually.
 pub fn animate(
@@ -161,7 +199,7 @@ pub fn animate(
     let mut props = new_armature.bones.clone();
     let mut frame = 0;
 
-    if armature.animations.len() != 0 && animation_index < armature.animations.len() - 1 {
+    if armature.animations.len() != 0 && animation_index < armature.animations.len() {
         let anim = &mut new_armature.animations[animation_index];
         if options.as_ref().unwrap().frame == None {
             frame = get_frame_by_time(anim, time.unwrap(), options.as_ref().unwrap().speed);
@@ -172,6 +210,45 @@ pub fn animate(
         props = rusty_skelform::animate(&mut new_armature, animation_index, frame, should_loop);
     }
 
+    // cross-fade from the previous animation's pose, if one was given and hasn't fully blended in
+    {
+        let o = options.as_ref().unwrap();
+        let last_anim_idx = o.last_anim_idx;
+        let blend_duration = o.blend_duration;
+        if blend_duration > 0.
+            && last_anim_idx != usize::MAX
+            && last_anim_idx != animation_index
+            && last_anim_idx < armature.animations.len()
+        {
+            let t = time
+                .map(|time| (time.elapsed().as_secs_f32() / blend_duration).clamp(0., 1.))
+                .unwrap_or(1.);
+
+            if t < 1. {
+                let last_anim_frame = o.last_anim_frame;
+                let old_frame = if last_anim_frame == -1 {
+                    new_armature.animations[last_anim_idx]
+                        .keyframes
+                        .iter()
+                        .map(|kf| kf.frame)
+                        .max()
+                        .unwrap_or(0)
+                } else {
+                    last_anim_frame
+                };
+
+                let old_props =
+                    rusty_skelform::animate(&mut new_armature, last_anim_idx, old_frame, should_loop);
+
+                for (new_prop, old_prop) in props.iter_mut().zip(old_props.iter()) {
+                    new_prop.pos = old_prop.pos.lerp(new_prop.pos, t);
+                    new_prop.scale = old_prop.scale.lerp(new_prop.scale, t);
+                    new_prop.rot = old_prop.rot + shortest_angle_delta(old_prop.rot, new_prop.rot) * t;
+                }
+            }
+        }
+    }
+
     let mut og_props = props.clone();
     rusty_skelform::inheritance(&mut og_props, HashMap::new());
     let ik_rots = rusty_skelform::inverse_kinematics(&og_props, &armature.ik_families);
@@ -185,15 +262,20 @@ pub fn animate(
     }
 
     if should_render {
-        draw_props(&mut props, &new_armature, texture);
+        draw_props(
+            &mut props,
+            &new_armature,
+            texture,
+            options.as_ref().unwrap().lights.as_ref(),
+        );
     }
 
     (props, frame)
 }
 
-/// Draw the provided props with Macroquad.
-pub fn draw_props(props: &mut Vec<Bone>, armature: &Armature, tex: &Texture2D) {
-    let col = Color::from_rgba(255, 255, 255, 255);
+/// Draw the provided props with Macroquad. `lights`, if given, tints sprites at the bone
+/// position and meshes per-vertex; `None` renders at full white.
+pub fn draw_props(props: &mut Vec<Bone>, armature: &Armature, tex: &Texture2D, lights: Option<&Lights>) {
     for p in 0..props.len() {
         if props[p].style_idxs.len() == 0 {
             continue;
@@ -203,38 +285,105 @@ pub fn draw_props(props: &mut Vec<Bone>, armature: &Armature, tex: &Texture2D) {
 
         // render bone as mesh
         if props[p].vertices.len() > 0 {
-            draw_mesh(&create_mesh(&props[p], prop_tex, tex));
+            draw_mesh(&create_mesh(&props[p], prop_tex, tex, lights));
             continue;
         }
 
-        let push_center = prop_tex.size / 2. * props[p].scale;
-
-        // render bone as regular rect
-        draw_texture_ex(
-            &tex,
-            props[p].pos.x - push_center.x,
-            props[p].pos.y - push_center.y,
-            col,
-            DrawTextureParams {
-                source: Some(Rect {
-                    x: prop_tex.offset.x,
-                    y: prop_tex.offset.y,
-                    w: prop_tex.size.x,
-                    h: prop_tex.size.y,
-                }),
-                dest_size: Some(macroquad::prelude::Vec2::new(
-                    prop_tex.size.x * props[p].scale.x,
-                    prop_tex.size.y * props[p].scale.y,
-                )),
-                rotation: props[p].rot,
-                ..Default::default()
-            },
-        );
+        let col = lights.map_or(Color::from_rgba(255, 255, 255, 255), |l| l.sample(props[p].pos));
+        draw_sprite_prop(&props[p], prop_tex, tex, col);
     }
 }
 
-/// Create Macroquad meshes from the given bones and texture data.
-pub fn create_mesh(bone: &Bone, bone_tex: &Texture, tex2d: &Texture2D) -> Mesh {
+/// Draw the provided props with Macroquad, batching each contiguous run of meshed bones into a
+/// single GPU-skinned draw call via `skinner` instead of one `draw_mesh` per bone. Sprite
+/// (non-meshed) bones still draw one call each. Batches are drawn in the same order as
+/// [`draw_props`], so a sprite bone interleaved between meshed bones still layers correctly
+/// relative to both. Falls back to the CPU path for any run that exceeds `skinner`'s
+/// `max_gpu_bones` — there's no runtime probe of the backend's actual uniform slot budget, so
+/// pick a `max_gpu_bones` that fits your target backend when constructing the [`GpuSkinner`].
+pub fn draw_props_gpu(
+    props: &mut Vec<Bone>,
+    armature: &Armature,
+    tex: &Texture2D,
+    skinner: &gpu_skin::GpuSkinner,
+    lights: Option<&Lights>,
+) {
+    let col = |pos: macroquad::prelude::Vec2| {
+        lights.map_or(Color::from_rgba(255, 255, 255, 255), |l| l.sample(pos))
+    };
+
+    let mut p = 0;
+    while p < props.len() {
+        if props[p].style_idxs.len() == 0 {
+            p += 1;
+            continue;
+        }
+
+        if props[p].vertices.len() == 0 {
+            let prop_tex = &armature.styles[0].textures[props[p].tex_idx as usize];
+            draw_sprite_prop(&props[p], prop_tex, tex, col(props[p].pos));
+            p += 1;
+            continue;
+        }
+
+        let start = p;
+        while p < props.len() && props[p].style_idxs.len() > 0 && props[p].vertices.len() > 0 {
+            p += 1;
+        }
+        let run: Vec<&Bone> = props[start..p].iter().collect();
+
+        if !skinner.draw(&run, armature, tex, lights) {
+            for bone in &run {
+                let prop_tex = &armature.styles[0].textures[bone.tex_idx as usize];
+                draw_mesh(&create_mesh(bone, prop_tex, tex, lights));
+            }
+        }
+    }
+}
+
+/// Draw a single non-meshed (sprite) bone as a textured rect.
+fn draw_sprite_prop(prop: &Bone, prop_tex: &Texture, tex: &Texture2D, col: Color) {
+    let push_center = prop_tex.size / 2. * prop.scale;
+
+    draw_texture_ex(
+        &tex,
+        prop.pos.x - push_center.x,
+        prop.pos.y - push_center.y,
+        col,
+        DrawTextureParams {
+            source: Some(Rect {
+                x: prop_tex.offset.x,
+                y: prop_tex.offset.y,
+                w: prop_tex.size.x,
+                h: prop_tex.size.y,
+            }),
+            dest_size: Some(macroquad::prelude::Vec2::new(
+                prop_tex.size.x * prop.scale.x,
+                prop_tex.size.y * prop.scale.y,
+            )),
+            rotation: prop.rot,
+            ..Default::default()
+        },
+    );
+}
+
+/// Shortest signed delta (in radians) from `from` to `to`, wrapped to `[-PI, PI]`.
+/// Used to interpolate rotations the short way around instead of snapping through a full turn.
+pub(crate) fn shortest_angle_delta(from: f32, to: f32) -> f32 {
+    let tau = std::f32::consts::TAU;
+    let mut delta = (to - from) % tau;
+    if delta > std::f32::consts::PI {
+        delta -= tau;
+    } else if delta < -std::f32::consts::PI {
+        delta += tau;
+    }
+    delta
+}
+
+/// Create Macroquad meshes from the given bones and texture data. `lights`, if given, is
+/// sampled per-vertex (plus an optional rim highlight facing each light) instead of the mesh
+/// being flat white.
+pub fn create_mesh(bone: &Bone, bone_tex: &Texture, tex2d: &Texture2D, lights: Option<&Lights>) -> Mesh {
     let mut mesh = Mesh {
         vertices: vec![],
         indices: vec![],
@@ -246,17 +395,33 @@ pub fn create_mesh(bone: &Bone, bone_tex: &Texture, tex2d: &Texture2D) -> Mesh {
     }
 
     for v in &bone.vertices {
-        let lt_tex_x = bone_tex.offset.x / tex2d.size().x;
-        let lt_tex_y = bone_tex.offset.y / tex2d.size().y;
-        let rb_tex_x = (bone_tex.offset.x + bone_tex.size.x) / tex2d.size().x;
-        let rb_tex_y = (bone_tex.offset.y + bone_tex.size.y) / tex2d.size().y;
+        let offset = macroquad::prelude::Vec2::new(
+            (v.pos.x - bone_tex.size.x / 2.) * bone.scale.x / 2.,
+            (-v.pos.y - bone_tex.size.y / 2.) * bone.scale.y / 2.,
+        );
+        let world_pos = bone.pos + offset;
+
+        let color = match lights {
+            Some(lights) => {
+                let tint = lights.sample(world_pos);
+                let rim = lights.rim(world_pos, offset);
+                Color::new(
+                    (tint.r + rim.r).min(1.),
+                    (tint.g + rim.g).min(1.),
+                    (tint.b + rim.b).min(1.),
+                    1.,
+                )
+            }
+            None => macroquad::color::WHITE,
+        };
+
         mesh.vertices.push(macroquad::models::Vertex::new(
-            bone.pos.x + ((v.pos.x - bone_tex.size.x / 2.) * bone.scale.x / 2.),
-            bone.pos.y + ((-v.pos.y - bone_tex.size.y / 2.) * bone.scale.y / 2.),
+            world_pos.x,
+            world_pos.y,
             0.,
             v.uv.x,
             v.uv.y,
-            macroquad::color::WHITE,
+            color,
         ));
     }
 
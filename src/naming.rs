@@ -0,0 +1,61 @@
+//! Named-bone addressing. [`BoneIndex`] resolves bone names to indices once at load time;
+//! [`retarget`] builds on it to replay a clip authored for one armature onto another that
+//! shares bone names.
+
+use rusty_skelform::{Animation, Armature};
+use std::collections::HashMap;
+
+/// A name -> bone index lookup, built once when an armature is loaded.
+pub struct BoneIndex(HashMap<String, usize>);
+
+impl BoneIndex {
+    /// Build the name -> index map for `armature`'s current bone order.
+    pub fn build(armature: &Armature) -> Self {
+        let mut map = HashMap::new();
+        for (i, bone) in armature.bones.iter().enumerate() {
+            map.insert(bone.name.clone(), i);
+        }
+        BoneIndex(map)
+    }
+
+    /// Index of the bone named `name`, if the armature has one.
+    pub fn bone_index(&self, name: &str) -> Option<usize> {
+        self.0.get(name).copied()
+    }
+
+    /// Immutable reference to the bone named `name` within `armature`.
+    pub fn bone<'a>(&self, armature: &'a Armature, name: &str) -> Option<&'a rusty_skelform::Bone> {
+        self.bone_index(name).map(|i| &armature.bones[i])
+    }
+
+    /// Mutable reference to the bone named `name` within `armature`.
+    pub fn bone_mut<'a>(
+        &self,
+        armature: &'a mut Armature,
+        name: &str,
+    ) -> Option<&'a mut rusty_skelform::Bone> {
+        self.bone_index(name).map(move |i| &mut armature.bones[i])
+    }
+}
+
+/// Play an animation authored for `source` onto a differently-indexed armature by matching
+/// bones by name. Keyframes for bones `target_index` doesn't recognize are dropped, so the
+/// target's rest pose carries through for them unchanged; this is how shared clips (idle,
+/// walk) get reused across rigs like `skellington` and `skellina` without re-authoring.
+pub fn retarget(source: &Armature, animation_index: usize, target_index: &BoneIndex) -> Animation {
+    let source_anim = &source.animations[animation_index];
+
+    let mut retargeted = source_anim.clone();
+    retargeted.keyframes = source_anim
+        .keyframes
+        .iter()
+        .filter_map(|kf| {
+            let bone_name = &source.bones[kf.bone_id].name;
+            let mut kf = kf.clone();
+            kf.bone_id = target_index.bone_index(bone_name)?;
+            Some(kf)
+        })
+        .collect();
+
+    retargeted
+}
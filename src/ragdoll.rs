@@ -0,0 +1,190 @@
+//! Verlet-integrated ragdoll. [`Ragdoll`] lets an armature switch from animated to physically
+//! simulated, for death flops and impact reactions.
+
+use crate::shortest_angle_delta;
+use macroquad::prelude::Vec2;
+use rusty_skelform::Bone;
+
+struct Particle {
+    pos: Vec2,
+    prev: Vec2,
+}
+
+struct Constraint {
+    bone: usize,
+    parent: usize,
+    length: f32,
+}
+
+/// A ragdoll seeded from one particle per bone, with a distance constraint along each
+/// parent -> child bone to keep the rig's proportions intact while it's simulated.
+pub struct Ragdoll {
+    particles: Vec<Particle>,
+    constraints: Vec<Constraint>,
+
+    pub gravity: Vec2,
+    /// Per-step velocity retention (1 = no damping).
+    pub damping: f32,
+    pub floor_y: f32,
+    pub constraint_iterations: usize,
+    /// Seconds to ease from the last animated pose into the full simulation.
+    pub blend_duration: f32,
+
+    blend_from: Vec<Bone>,
+    blend_elapsed: f32,
+    active: bool,
+}
+
+impl Ragdoll {
+    pub fn new(gravity: Vec2, damping: f32, floor_y: f32) -> Self {
+        Ragdoll {
+            particles: vec![],
+            constraints: vec![],
+            gravity,
+            damping,
+            floor_y,
+            constraint_iterations: 4,
+            blend_duration: 0.2,
+            blend_from: vec![],
+            blend_elapsed: 0.,
+            active: false,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Seed a particle at each bone's current world position and a distance constraint along
+    /// each parent -> child bone, taken from the armature's last animated pose.
+    pub fn activate(&mut self, props: &[Bone]) {
+        self.particles = props
+            .iter()
+            .map(|b| Particle {
+                pos: b.pos,
+                prev: b.pos,
+            })
+            .collect();
+
+        self.constraints = props
+            .iter()
+            .enumerate()
+            .filter_map(|(i, bone)| {
+                if bone.parent_id < 0 {
+                    return None;
+                }
+                let parent = bone.parent_id as usize;
+                Some(Constraint {
+                    bone: i,
+                    parent,
+                    length: props[i].pos.distance(props[parent].pos),
+                })
+            })
+            .collect();
+
+        self.blend_from = props.to_vec();
+        self.blend_elapsed = 0.;
+        self.active = true;
+    }
+
+    /// Kick the particle nearest `at` with `impulse`, e.g. on a hit.
+    pub fn impulse(&mut self, at: Vec2, impulse: Vec2) {
+        let nearest = self
+            .particles
+            .iter_mut()
+            .min_by(|a, b| a.pos.distance(at).total_cmp(&b.pos.distance(at)));
+
+        if let Some(p) = nearest {
+            p.prev -= impulse;
+        }
+    }
+
+    /// Integrate the simulation by `dt` seconds and write the resolved positions/rotations
+    /// back into `props`, easing in from the pose passed to [`Ragdoll::activate`] over
+    /// `blend_duration`.
+    pub fn update(&mut self, props: &mut [Bone], dt: f32) {
+        if !self.active {
+            return;
+        }
+
+        for p in &mut self.particles {
+            let next = p.pos + (p.pos - p.prev) * self.damping + self.gravity * dt * dt;
+            p.prev = p.pos;
+            p.pos = next;
+
+            if p.pos.y > self.floor_y {
+                p.pos.y = self.floor_y;
+            }
+        }
+
+        for _ in 0..self.constraint_iterations {
+            for c in &self.constraints {
+                let delta = self.particles[c.parent].pos - self.particles[c.bone].pos;
+                let dist = delta.length();
+                if dist == 0. {
+                    continue;
+                }
+                let correction = delta * (0.5 * (dist - c.length) / dist);
+                self.particles[c.bone].pos += correction;
+                self.particles[c.parent].pos -= correction;
+            }
+        }
+
+        self.blend_elapsed += dt;
+        let t = if self.blend_duration > 0. {
+            (self.blend_elapsed / self.blend_duration).clamp(0., 1.)
+        } else {
+            1.
+        };
+
+        for c in &self.constraints {
+            let dir = self.particles[c.parent].pos - self.particles[c.bone].pos;
+            if dir.length_squared() == 0. {
+                continue;
+            }
+            let sim_rot = dir.y.atan2(dir.x);
+            let animated_rot = self.blend_from.get(c.bone).map_or(props[c.bone].rot, |b| b.rot);
+            props[c.bone].rot = animated_rot + shortest_angle_delta(animated_rot, sim_rot) * t;
+        }
+
+        // root bones never appear as the child side of a constraint, so the loop above never
+        // touches them; derive their rotation from the average direction toward their own
+        // children instead, otherwise the hip/root bone would slide without ever tipping over
+        for (i, prop) in props.iter_mut().enumerate() {
+            if prop.parent_id >= 0 {
+                continue;
+            }
+
+            let children: Vec<usize> = self
+                .constraints
+                .iter()
+                .filter(|c| c.parent == i)
+                .map(|c| c.bone)
+                .collect();
+
+            if children.is_empty() {
+                continue;
+            }
+
+            let root_pos = self.particles[i].pos;
+            let avg_dir = children
+                .iter()
+                .map(|&c| self.particles[c].pos - root_pos)
+                .fold(Vec2::new(0., 0.), |acc, d| acc + d)
+                / children.len() as f32;
+
+            if avg_dir.length_squared() == 0. {
+                continue;
+            }
+
+            let sim_rot = avg_dir.y.atan2(avg_dir.x);
+            let animated_rot = self.blend_from.get(i).map_or(prop.rot, |b| b.rot);
+            prop.rot = animated_rot + shortest_angle_delta(animated_rot, sim_rot) * t;
+        }
+
+        for (i, prop) in props.iter_mut().enumerate() {
+            let animated_pos = self.blend_from.get(i).map_or(prop.pos, |b| b.pos);
+            prop.pos = animated_pos.lerp(self.particles[i].pos, t);
+        }
+    }
+}
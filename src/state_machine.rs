@@ -0,0 +1,201 @@
+//! Declarative animation state machine built on top of [`crate::animate`].
+//!
+//! [`StateMachine`] owns a graph of named states and transitions, driving the cross-fade
+//! blending in [`crate::animate`] automatically whenever the active state changes.
+
+use crate::{animate, AnimOptions};
+use macroquad::prelude::{Texture2D, Vec2};
+use rusty_skelform::{get_frame_by_time, Armature, Bone};
+use std::{collections::HashMap, time::Instant};
+
+/// One named animation state in a [`StateMachine`].
+pub struct AnimState {
+    pub animation_index: usize,
+    pub should_loop: bool,
+    pub speed: f32,
+    pub pos_offset: Vec2,
+    pub scale_factor: f32,
+}
+
+impl Default for AnimState {
+    fn default() -> Self {
+        AnimState {
+            animation_index: 0,
+            should_loop: true,
+            speed: 1.,
+            pos_offset: Vec2::new(0., 0.),
+            scale_factor: 0.25,
+        }
+    }
+}
+
+/// An edge in the state graph. `from: None` makes it an "any-state" transition, evaluated
+/// regardless of the currently active state (e.g. jumping from any grounded state). `condition`
+/// is expected to be level-triggered (true for as long as the underlying input holds) rather
+/// than edge-triggered: [`StateMachine::update`] never re-enters a transition whose `to` is
+/// already the active state, so a condition that stays true after arriving won't keep resetting
+/// the animation. Repeatedly restarting the same state from scratch isn't supported.
+pub struct Transition {
+    pub from: Option<String>,
+    pub to: String,
+    pub condition: Box<dyn Fn() -> bool>,
+    pub blend_duration: f32,
+}
+
+impl Transition {
+    /// A transition that only applies while `from` is the active state.
+    pub fn new(
+        from: impl Into<String>,
+        to: impl Into<String>,
+        blend_duration: f32,
+        condition: impl Fn() -> bool + 'static,
+    ) -> Self {
+        Transition {
+            from: Some(from.into()),
+            to: to.into(),
+            condition: Box::new(condition),
+            blend_duration,
+        }
+    }
+
+    /// A transition that applies no matter which state is currently active.
+    pub fn any(
+        to: impl Into<String>,
+        blend_duration: f32,
+        condition: impl Fn() -> bool + 'static,
+    ) -> Self {
+        Transition {
+            from: None,
+            to: to.into(),
+            condition: Box::new(condition),
+            blend_duration,
+        }
+    }
+}
+
+/// A graph of named [`AnimState`]s connected by [`Transition`]s, driving [`crate::animate`]'s
+/// cross-fade blending as the active state changes.
+pub struct StateMachine {
+    states: HashMap<String, AnimState>,
+    transitions: Vec<Transition>,
+
+    active: String,
+    previous: Option<String>,
+    /// Frame the previous state had actually reached when the transition out of it happened,
+    /// matching [`AnimOptions::last_anim_frame`].
+    previous_frame: i32,
+    blend_duration: f32,
+    state_entered: Instant,
+}
+
+impl StateMachine {
+    pub fn new(initial_state: impl Into<String>) -> Self {
+        StateMachine {
+            states: HashMap::new(),
+            transitions: vec![],
+            active: initial_state.into(),
+            previous: None,
+            previous_frame: -1,
+            blend_duration: 0.15,
+            state_entered: Instant::now(),
+        }
+    }
+
+    pub fn add_state(mut self, name: impl Into<String>, state: AnimState) -> Self {
+        self.states.insert(name.into(), state);
+        self
+    }
+
+    pub fn add_transition(mut self, transition: Transition) -> Self {
+        self.transitions.push(transition);
+        self
+    }
+
+    /// Name of the currently active state.
+    pub fn active_state(&self) -> &str {
+        &self.active
+    }
+
+    /// Leave the active state for `to`, recording the frame it had actually reached (rather
+    /// than assuming its final frame) so the outgoing cross-fade starts from where the state
+    /// really was, e.g. mid-stride rather than standing.
+    fn enter(&mut self, armature: &mut Armature, to: String, blend_duration: f32) {
+        let outgoing = self
+            .states
+            .get(&self.active)
+            .map(|s| (s.animation_index, s.speed));
+
+        self.previous_frame = match outgoing {
+            Some((animation_index, speed)) => get_frame_by_time(
+                &mut armature.animations[animation_index],
+                self.state_entered,
+                speed,
+            ),
+            None => -1,
+        };
+
+        self.previous = Some(self.active.clone());
+        self.active = to;
+        self.blend_duration = blend_duration;
+        self.state_entered = Instant::now();
+    }
+
+    /// Evaluate transitions, advance the active state's animation and return the resulting
+    /// props, identically to [`crate::animate`]. `dt` is unused for timing (animations are
+    /// timed off of when the state was entered, same as [`crate::animate`]'s `time` parameter)
+    /// but is accepted so callers can pass their frame delta uniformly with the rest of the
+    /// game loop.
+    pub fn update(&mut self, armature: &mut Armature, texture: &Texture2D, _dt: f32) -> (Vec<Bone>, i32) {
+        // conditions are expected to be level-triggered (e.g. "velocity > 0"), so a transition
+        // whose `to` is already the active state is skipped rather than re-entered every frame
+        // it stays true, which would otherwise keep resetting state_entered and freeze the
+        // animation at frame 0 forever
+        for i in 0..self.transitions.len() {
+            let applies = match &self.transitions[i].from {
+                Some(from) => *from == self.active,
+                None => true,
+            };
+            if !applies || self.transitions[i].to == self.active {
+                continue;
+            }
+            if (self.transitions[i].condition)() {
+                let to = self.transitions[i].to.clone();
+                let blend_duration = self.transitions[i].blend_duration;
+                self.enter(armature, to, blend_duration);
+                break;
+            }
+        }
+
+        let state = self
+            .states
+            .get(&self.active)
+            .expect("StateMachine::update: active state was never registered with add_state");
+
+        let last_anim_idx = self
+            .previous
+            .as_ref()
+            .and_then(|name| self.states.get(name))
+            .map(|s| s.animation_index)
+            .unwrap_or(usize::MAX);
+
+        let options = AnimOptions {
+            speed: state.speed,
+            pos_offset: state.pos_offset,
+            scale_factor: state.scale_factor,
+            last_anim_idx,
+            last_anim_frame: self.previous_frame,
+            blend_duration: self.blend_duration,
+            ..Default::default()
+        };
+
+        animate(
+            armature,
+            texture,
+            state.animation_index,
+            Some(self.state_entered),
+            state.should_loop,
+            true,
+            Some(options),
+        )
+    }
+}